@@ -0,0 +1,48 @@
+//! The `Backend` trait: a platform-specific terminal implementation that `core::driver::Driver`
+//! delegates to. `core::unix::UnixBackend` drives a Unix terminal via terminfo (or a built-in
+//! ANSI/xterm definition); `core::windows::WindowsBackend` drives the Win32 Console API instead.
+//! Selecting between them at compile time via `#[cfg(unix)]`/`#[cfg(windows)]` lets downstream
+//! code depend only on `Driver`, without caring which backend is underneath.
+
+use core::input::Match;
+
+// Driver capabilities are an enum instead of string constants so callers get compile-time
+// type-checking instead of hoping invalid strings aren't passed, and so a `Backend` doesn't
+// need a hard-coded method per capability it wants to expose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DevFn {
+    EnterCa,
+    ExitCa,
+    EnterXmit,
+    ExitXmit,
+    ShowCursor,
+    HideCursor,
+    SetCursor(usize, usize),
+    Clear,
+    Reset,
+    Underline,
+    Bold,
+    Blink,
+    Reverse,
+    SetFg(u8),
+    SetBg(u8),
+    EnableMouse,
+    DisableMouse,
+}
+
+// A platform-specific terminal implementation: turns raw input into `Event`s, and `DevFn`s into
+// the bytes that must be written to produce that effect.
+pub trait Backend {
+    // Matches the start of `buf` against this terminal's known escape sequences. Returns
+    // `Match::Event` once a full sequence (or a plain character) has been read, `Match::Partial`
+    // if `buf` is a prefix of some sequence and the caller should read more before feeding
+    // again, or `Match::None` if `buf` can't possibly be the start of a known sequence.
+    //
+    // `buf` is raw bytes rather than `&str`: some escape sequences (notably X10 mouse reports)
+    // carry single bytes of 128 or above that aren't valid UTF-8 on their own.
+    fn feed(&self, buf: &[u8]) -> Match;
+
+    // Returns the device specific escape sequence for the given `DevFn`, or None if the
+    // terminal lacks the capability to perform the specified function.
+    fn get(&self, dfn: DevFn) -> Option<Vec<u8>>;
+}
@@ -0,0 +1,38 @@
+//! Input event types produced by a `core::backend::Backend`.
+
+// An input event read from the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Char(char),
+    Function(u8),
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Mouse {
+        x: u16,
+        y: u16,
+        button: u8,
+        pressed: bool,
+        // Set when the report carries xterm's motion bit (`0x20`), meaning the button was
+        // already held down while the pointer moved, rather than this being a fresh press.
+        drag: bool,
+    },
+}
+
+// The result of matching the start of an input buffer against known escape sequences, as
+// returned by `core::backend::Backend::feed`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Match {
+    // A full event was matched, consuming this many bytes of the fed buffer.
+    Event(Event, usize),
+    // The buffer is a prefix of some known sequence; the caller should read more and feed
+    // again before giving up on it.
+    Partial,
+    // The buffer doesn't match the start of any known sequence.
+    None,
+}
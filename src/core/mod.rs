@@ -0,0 +1,8 @@
+pub mod driver;
+pub mod backend;
+pub mod input;
+
+#[cfg(unix)]
+mod unix;
+#[cfg(windows)]
+mod windows;
@@ -0,0 +1,548 @@
+// Temporary fix before certain constants are used.
+#![allow(dead_code)]
+
+use std::env;
+use std::io::{Error, ErrorKind};
+use std::collections::HashMap;
+use std::str;
+
+use term::terminfo::TermInfo;
+use term::terminfo::parm;
+use term::terminfo::parm::{Param, Variables};
+
+use core::backend::{Backend, DevFn};
+use core::input::{Event, Match};
+use terminal::{self, Terminal};
+
+// Array of tuples of events and their corresponding terminal keys.
+// Tuples are of the form (event, variable_name, cap_name).
+// Both the variable_name and cap_name are given since terminfo
+// uses a combination of variable and cap names.
+const KEYS: &'static [(Event, &'static str, &'static str)] = &[
+    (Event::Function(1), "key_f1", "kf1"),
+    (Event::Function(2), "key_f2", "kf2"),
+    (Event::Function(3), "key_f3", "kf3"),
+    (Event::Function(4), "key_f4", "kf4"),
+    (Event::Function(5), "key_f5", "kf5"),
+    (Event::Function(6), "key_f6", "kf6"),
+    (Event::Function(7), "key_f7", "kf7"),
+    (Event::Function(8), "key_f8", "kf8"),
+    (Event::Function(9), "key_f9", "kf9"),
+    (Event::Function(10), "key_f10", "kf10"),
+    (Event::Function(11), "key_f11", "kf11"),
+    (Event::Function(12), "key_f12", "kf12"),
+    (Event::Up, "key_up", "kcuu1"),
+    (Event::Down, "key_down", "kcud1"),
+    (Event::Left, "key_left", "kcub1"),
+    (Event::Right, "key_right", "kcuf1"),
+    (Event::PageUp, "key_ppage", "kpp"),
+    (Event::PageDown, "key_npage", "knp"),
+    (Event::Home, "key_home", "khome"),
+    (Event::End, "key_end", "kend"),
+];
+
+const ESCAPE: u8 = 0x1b;
+
+// String constants correspond to terminfo capnames and are used inside the module for convenience.
+const ENTER_CA: &'static str = "smcup";
+const EXIT_CA: &'static str = "rmcup";
+const ENTER_XMIT: &'static str = "smkx";
+const EXIT_XMIT: &'static str = "rmkx";
+const SHOW_CURSOR: &'static str = "cnorm";
+const HIDE_CURSOR: &'static str = "civis";
+const SET_CURSOR: &'static str = "cup";
+const CLEAR: &'static str = "clear";
+const RESET: &'static str = "sgr0";
+const UNDERLINE: &'static str = "smul";
+const BOLD: &'static str = "bold";
+const BLINK: &'static str = "blink";
+const REVERSE: &'static str = "rev";
+const SETFG: &'static str = "setaf";
+const SETBG: &'static str = "setab";
+
+// Maps a `DevFn` to its terminfo capname. Only meaningful for the terminfo path; the built-in
+// fallback serves `DevFn`s from `Terminal::funcs` by position instead (see `get_builtin`).
+fn capname(dfn: &DevFn) -> &'static str {
+    match *dfn {
+        DevFn::EnterCa => ENTER_CA,
+        DevFn::ExitCa => EXIT_CA,
+        DevFn::EnterXmit => ENTER_XMIT,
+        DevFn::ExitXmit => EXIT_XMIT,
+        DevFn::ShowCursor => SHOW_CURSOR,
+        DevFn::HideCursor => HIDE_CURSOR,
+        DevFn::SetCursor(..) => SET_CURSOR,
+        DevFn::Clear => CLEAR,
+        DevFn::Reset => RESET,
+        DevFn::Underline => UNDERLINE,
+        DevFn::Bold => BOLD,
+        DevFn::Blink => BLINK,
+        DevFn::Reverse => REVERSE,
+        DevFn::SetFg(..) => SETFG,
+        DevFn::SetBg(..) => SETBG,
+        // Mouse tracking isn't a terminfo capability; `get` handles it before reaching here.
+        DevFn::EnableMouse | DevFn::DisableMouse => unreachable!(),
+    }
+}
+
+// Mouse tracking isn't sourced from terminfo or a built-in `Terminal` table: it's a fixed
+// xterm protocol extension, emitted the same way regardless of backend source. SGR mode
+// (1006) is enabled alongside the legacy X10 mode (1000) so callers get usable coordinates
+// beyond column/row 223, which X10's single-byte encoding can't represent.
+const MOUSE_ENABLE: &'static str = "\x1b[?1000h\x1b[?1006h";
+const MOUSE_DISABLE: &'static str = "\x1b[?1000l\x1b[?1006l";
+
+// A node in the escape-sequence trie: `children` are the possible next bytes, and `event` is
+// set when this node is itself the end of a known sequence.
+struct TrieNode {
+    children: HashMap<u8, TrieNode>,
+    event: Option<Event>,
+}
+
+impl TrieNode {
+    fn new() -> TrieNode {
+        TrieNode {
+            children: HashMap::new(),
+            event: None,
+        }
+    }
+}
+
+// Maps escape sequences to the `Event`s they represent, letting `feed` tell an incomplete
+// prefix of a real sequence (keep reading) apart from bytes that can't be one (flush them).
+// A flat `HashMap` can't make that distinction: it only matches on a fully assembled string.
+struct Trie {
+    root: TrieNode,
+}
+
+impl Trie {
+    fn new() -> Trie {
+        Trie { root: TrieNode::new() }
+    }
+
+    fn insert(&mut self, seq: &str, event: Event) {
+        let mut node = &mut self.root;
+        for b in seq.bytes() {
+            node = node.children.entry(b).or_insert_with(TrieNode::new);
+        }
+        node.event = Some(event);
+    }
+
+    // Walks `buf` byte by byte: returns `Match::Event` with the number of bytes consumed as
+    // soon as a terminal node is reached, `Match::Partial` if `buf` runs out on a valid
+    // interior node, or `Match::None` as soon as a byte doesn't extend any known sequence.
+    fn feed(&self, buf: &[u8]) -> Match {
+        let mut node = &self.root;
+        let mut consumed = 0;
+
+        for &b in buf {
+            node = match node.children.get(&b) {
+                Some(next) => next,
+                None => return Match::None,
+            };
+            consumed += 1;
+
+            if let Some(event) = node.event {
+                return Match::Event(event, consumed);
+            }
+        }
+
+        Match::Partial
+    }
+}
+
+// Where a `UnixBackend` gets its escape sequences from: either a parsed terminfo entry, or one
+// of the hard-coded `Terminal` definitions used when no terminfo database is available.
+enum Source {
+    Terminfo(TermInfo),
+    Builtin(&'static Terminal),
+}
+
+// Drives a Unix terminal, either via the terminfo database or, failing that, a built-in
+// ANSI/xterm-compatible definition.
+pub struct UnixBackend {
+    source: Source,
+    trie: Trie,
+}
+
+impl UnixBackend {
+    // Creates a new `UnixBackend`, using the terminfo database for the current `$TERM`.
+    //
+    // If no terminfo entry can be found, falls back to a built-in ANSI/xterm-compatible
+    // definition when `$TERM` looks like one of the terminals in `terminal::lookup`, so the
+    // library still works on systems with no terminfo database installed at all.
+    pub fn new() -> Result<UnixBackend, Error> {
+        match TermInfo::from_env() {
+            Ok(tinfo) => UnixBackend::from_terminfo(tinfo),
+            Err(e) => {
+                env::var("TERM").ok()
+                    .as_ref()
+                    .and_then(|term| terminal::lookup(term))
+                    .map(UnixBackend::with_builtin)
+                    .ok_or_else(|| e.into())
+            }
+        }
+    }
+
+    fn from_terminfo(tinfo: TermInfo) -> Result<UnixBackend, Error> {
+        let mut backend = UnixBackend {
+            source: Source::Terminfo(tinfo),
+            trie: Trie::new(),
+        };
+
+        try!(backend.insert_terminfo_keys());
+
+        Ok(backend)
+    }
+
+    // Creates a `UnixBackend` from a built-in `Terminal` definition, bypassing terminfo entirely.
+    pub fn with_builtin(term: &'static Terminal) -> UnixBackend {
+        let mut backend = UnixBackend {
+            source: Source::Builtin(term),
+            trie: Trie::new(),
+        };
+
+        backend.insert_builtin_keys(term);
+
+        backend
+    }
+
+    // Inserts every escape sequence terminfo has for this terminal into `trie`.
+    //
+    // Most terminals don't advertise every capability in `KEYS` (missing `key_f11` or `kpp`
+    // is common), so a missing entry simply isn't inserted rather than failing the whole
+    // backend. This function only errors out if a capability that *is* present turns out not
+    // to be valid UTF-8.
+    fn insert_terminfo_keys(&mut self) -> Result<(), Error> {
+        let tinfo = match self.source {
+            Source::Terminfo(ref tinfo) => tinfo,
+            Source::Builtin(..) => unreachable!(),
+        };
+        let strings = &tinfo.strings;
+        for &(event, variable, cap_name) in KEYS {
+            let escape_seq_utf8 = match strings.get(variable).or_else(|| strings.get(cap_name)) {
+                Some(seq) => seq,
+                None => continue,
+            };
+
+            let escape_seq_str = try!(str::from_utf8(escape_seq_utf8).or(Err(Error::new(ErrorKind::InvalidData,
+                format!("terminal escape sequence for (variable: {}, cap_name{}) is invalid utf8",
+                        variable, cap_name)))));
+
+            self.trie.insert(escape_seq_str, event);
+        }
+
+        Ok(())
+    }
+
+    // Inserts a built-in `Terminal`'s `keys` table into `trie`, which is indexed to align
+    // with `KEYS` (see `terminal::XTERM_KEYS` for the ordering).
+    fn insert_builtin_keys(&mut self, term: &'static Terminal) {
+        for (&(event, _, _), seq) in KEYS.iter().zip(term.keys.iter()) {
+            self.trie.insert(seq, event);
+        }
+    }
+
+    // Serves a `DevFn` directly from a built-in `Terminal`'s `funcs` table, which is indexed
+    // to align with the non-parameterized `DevFn` variants (see `terminal::XTERM_FUNCS` for the
+    // ordering). The parameterized variants aren't present in `funcs`, since built-in
+    // definitions only capture fixed strings, so they're synthesized as standard ANSI/xterm
+    // sequences instead.
+    fn get_builtin(term: &'static Terminal, dfn: DevFn) -> Option<Vec<u8>> {
+        let idx = match dfn {
+            DevFn::EnterCa => 0,
+            DevFn::ExitCa => 1,
+            DevFn::EnterXmit => 2,
+            DevFn::ExitXmit => 3,
+            DevFn::ShowCursor => 4,
+            DevFn::HideCursor => 5,
+            DevFn::Clear => 6,
+            DevFn::Reset => 7,
+            DevFn::Underline => 8,
+            DevFn::Bold => 9,
+            DevFn::Blink => 10,
+            DevFn::Reverse => 11,
+            DevFn::SetCursor(x, y) => return Some(format!("\x1b[{};{}H", y + 1, x + 1).into_bytes()),
+            DevFn::SetFg(attr) => return Some(format!("\x1b[38;5;{}m", attr).into_bytes()),
+            DevFn::SetBg(attr) => return Some(format!("\x1b[48;5;{}m", attr).into_bytes()),
+            // Mouse tracking is handled directly in `get`, before this is ever reached.
+            DevFn::EnableMouse | DevFn::DisableMouse => unreachable!(),
+        };
+
+        term.funcs.get(idx).map(|seq| seq.as_bytes().to_vec())
+    }
+}
+
+impl Backend for UnixBackend {
+    fn feed(&self, buf: &[u8]) -> Match {
+        let first = match buf.first() {
+            Some(&b) => b,
+            None => return Match::Partial,
+        };
+
+        if first != ESCAPE {
+            return decode_char(buf);
+        }
+
+        // A lone ESC is a valid prefix of every sequence below, so this is `Partial` rather
+        // than `Event::Char(ESCAPE)`; it's up to the caller to fall back to the literal
+        // escape character once a read timeout proves nothing else is coming.
+        let rest = &buf[1..];
+        if rest.is_empty() {
+            return Match::Partial;
+        }
+
+        if let Some(m) = match_mouse_event(rest) {
+            return m;
+        }
+
+        self.trie.feed(buf)
+    }
+
+    // An absent capability (e.g. `blink` or `smcup` on a terminal that doesn't support it) is
+    // not an error: the terminfo/built-in lookup already returns `None` for it, which this
+    // function just passes straight through to the caller.
+    fn get(&self, dfn: DevFn) -> Option<Vec<u8>> {
+        match dfn {
+            DevFn::EnableMouse => return Some(MOUSE_ENABLE.as_bytes().to_vec()),
+            DevFn::DisableMouse => return Some(MOUSE_DISABLE.as_bytes().to_vec()),
+            _ => {}
+        }
+
+        match self.source {
+            Source::Terminfo(ref tinfo) => {
+                tinfo.strings.get(capname(&dfn)).map(|cap| {
+                    match dfn {
+                        DevFn::SetFg(attr) |
+                        DevFn::SetBg(attr) => {
+                            let params = &[Param::Number(attr as i32)];
+                            let mut vars = Variables::new();
+                            parm::expand(cap, params, &mut vars).unwrap()
+                        }
+                        DevFn::SetCursor(x, y) => {
+                            let params = &[Param::Number(y as i32), Param::Number(x as i32)];
+                            let mut vars = Variables::new();
+                            parm::expand(cap, params, &mut vars).unwrap()
+                        }
+                        _ => cap.clone(),
+                    }
+                })
+            }
+            Source::Builtin(term) => UnixBackend::get_builtin(term, dfn),
+        }
+    }
+}
+
+// Decodes the UTF-8 character at the start of `buf` as an `Event::Char` along with its byte
+// length. Returns `Match::Partial` if `buf` is a valid but incomplete prefix of a multi-byte
+// character, so the caller can wait for more bytes rather than misreading it, and
+// `Match::None` if `buf` starts with bytes that can't begin a UTF-8 character at all.
+fn decode_char(buf: &[u8]) -> Match {
+    let valid = match str::from_utf8(buf) {
+        Ok(s) => s,
+        Err(ref e) if e.valid_up_to() > 0 => str::from_utf8(&buf[..e.valid_up_to()]).unwrap(),
+        Err(ref e) if e.error_len().is_some() => return Match::None,
+        Err(_) => return Match::Partial,
+    };
+
+    match valid.chars().next() {
+        Some(c) => Match::Event(Event::Char(c), c.len_utf8()),
+        None => Match::Partial,
+    }
+}
+
+// Tries to match `rest` (the part of `buf` right after the leading ESC) as an xterm mouse
+// report. Returns None if it isn't one at all, so the caller falls through to the trie; a
+// truncated report is `Some(Match::Partial)` rather than None, since it might still become
+// one once more bytes arrive.
+fn match_mouse_event(rest: &[u8]) -> Option<Match> {
+    if rest.starts_with(b"[M") {
+        Some(match_x10_mouse_event(&rest[b"[M".len()..]))
+    } else if rest.starts_with(b"[<") {
+        Some(match_sgr_mouse_event(&rest[b"[<".len()..]))
+    } else {
+        None
+    }
+}
+
+// X10 mouse reports are three bytes, `Cb Cx Cy`, each the encoded value plus 32. The low two
+// bits of `Cb` give the button (3 means release). `Cx`/`Cy` are 1-based coordinates and, like
+// `Cb`, are single raw bytes rather than UTF-8: a coordinate of 96 or above encodes to a byte
+// of 128 or above, which doesn't stand on its own as valid UTF-8. Working on `&[u8]` here
+// (rather than `&str`, which couldn't represent such a byte without replacing or rejecting it)
+// is what lets those coordinates decode correctly at all.
+fn match_x10_mouse_event(body: &[u8]) -> Match {
+    let mut consumed = 1 + b"[M".len();
+    let mut fields = [0i32; 3];
+
+    for (i, field) in fields.iter_mut().enumerate() {
+        let b = match body.get(i) {
+            Some(&b) => b,
+            None => return Match::Partial,
+        };
+        *field = b as i32 - 32;
+        consumed += 1;
+    }
+
+    let button = fields[0] & 0x3;
+    Match::Event(Event::Mouse {
+        x: fields[1] as u16,
+        y: fields[2] as u16,
+        button: button as u8,
+        pressed: button != 3,
+        drag: fields[0] & 0x20 != 0,
+    }, consumed)
+}
+
+// SGR mouse reports are `b ; x ; y` followed by `M` (press) or `m` (release), with `b`, `x` and
+// `y` written out as ASCII decimal digits (unlike X10, so no raw high-bit byte to worry about
+// here).
+fn match_sgr_mouse_event(body: &[u8]) -> Match {
+    let end = match body.iter().position(|&b| b == b'M' || b == b'm') {
+        Some(idx) => idx,
+        None => return Match::Partial,
+    };
+    let pressed = body[end] == b'M';
+    let consumed = 1 + b"[<".len() + end + 1;
+
+    let text = match str::from_utf8(&body[..end]) {
+        Ok(text) => text,
+        Err(_) => return Match::None,
+    };
+
+    let mut fields = text.splitn(3, ';');
+    let button = fields.next().and_then(|s| s.parse::<i32>().ok());
+    let x = fields.next().and_then(|s| s.parse::<u16>().ok());
+    let y = fields.next().and_then(|s| s.parse::<u16>().ok());
+
+    match (button, x, y) {
+        (Some(button), Some(x), Some(y)) => {
+            Match::Event(Event::Mouse {
+                x: x,
+                y: y,
+                button: (button & 0x3) as u8,
+                pressed: pressed,
+                drag: button & 0x20 != 0,
+            }, consumed)
+        }
+        _ => Match::None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::input::{Event, Match};
+
+    #[test]
+    fn trie_matches_full_sequence_and_reports_bytes_consumed() {
+        let mut trie = Trie::new();
+        trie.insert("\x1bOP", Event::Function(1));
+        trie.insert("\x1b[15~", Event::Function(5));
+
+        assert_eq!(trie.feed(b"\x1bOP"), Match::Event(Event::Function(1), 3));
+        assert_eq!(trie.feed(b"\x1b[15~"), Match::Event(Event::Function(5), 5));
+    }
+
+    #[test]
+    fn trie_reports_partial_on_incomplete_prefix() {
+        let mut trie = Trie::new();
+        trie.insert("\x1bOP", Event::Function(1));
+
+        assert_eq!(trie.feed(b"\x1bO"), Match::Partial);
+        assert_eq!(trie.feed(b"\x1b"), Match::Partial);
+    }
+
+    #[test]
+    fn trie_reports_none_once_a_byte_cant_extend_any_sequence() {
+        let mut trie = Trie::new();
+        trie.insert("\x1bOP", Event::Function(1));
+
+        assert_eq!(trie.feed(b"\x1bZ"), Match::None);
+    }
+
+    #[test]
+    fn x10_mouse_event_decodes_button_and_coordinates() {
+        let body = [32u8 + 0, 32 + 5, 32 + 10];
+
+        match match_x10_mouse_event(&body) {
+            Match::Event(Event::Mouse { x, y, button, pressed, drag }, consumed) => {
+                assert_eq!((x, y, button, pressed, drag), (5, 10, 0, true, false));
+                assert_eq!(consumed, 1 + b"[M".len() + 3);
+            }
+            other => panic!("expected a mouse event, got {:?}", other),
+        }
+    }
+
+    // Coordinates of 96 or above encode to a byte of 128 or above, which can't stand on its
+    // own as valid UTF-8 — the reason `match_x10_mouse_event` takes `&[u8]` rather than `&str`.
+    #[test]
+    fn x10_mouse_event_decodes_coordinates_at_and_past_the_96_column_boundary() {
+        let body = [32u8 + 0, 32u8 + 96, 32u8 + 150];
+        assert!(str::from_utf8(&body).is_err());
+
+        match match_x10_mouse_event(&body) {
+            Match::Event(Event::Mouse { x, y, .. }, _) => assert_eq!((x, y), (96, 150)),
+            other => panic!("expected a mouse event, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn x10_mouse_event_sets_drag_from_the_motion_bit() {
+        let body = [32u8 + 0x20, 32 + 5, 32 + 10];
+
+        match match_x10_mouse_event(&body) {
+            Match::Event(Event::Mouse { drag, .. }, _) => assert!(drag),
+            other => panic!("expected a mouse event, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn x10_mouse_event_is_partial_when_truncated() {
+        assert_eq!(match_x10_mouse_event(b"\x20\x20"), Match::Partial);
+    }
+
+    #[test]
+    fn sgr_mouse_event_decodes_button_and_coordinates() {
+        match match_sgr_mouse_event(b"0;5;10M") {
+            Match::Event(Event::Mouse { x, y, button, pressed, drag }, consumed) => {
+                assert_eq!((x, y, button, pressed, drag), (5, 10, 0, true, false));
+                assert_eq!(consumed, 1 + b"[<".len() + "0;5;10M".len());
+            }
+            other => panic!("expected a mouse event, got {:?}", other),
+        }
+    }
+
+    // Unlike X10, SGR coordinates are ASCII decimal text, so they aren't limited to a single
+    // byte and have no equivalent encoding boundary.
+    #[test]
+    fn sgr_mouse_event_decodes_coordinates_past_the_x10_column_boundary() {
+        match match_sgr_mouse_event(b"0;150;200M") {
+            Match::Event(Event::Mouse { x, y, .. }, _) => assert_eq!((x, y), (150, 200)),
+            other => panic!("expected a mouse event, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn sgr_mouse_event_release_is_lowercase_m() {
+        match match_sgr_mouse_event(b"0;5;10m") {
+            Match::Event(Event::Mouse { pressed, .. }, _) => assert!(!pressed),
+            other => panic!("expected a mouse event, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn sgr_mouse_event_sets_drag_from_the_motion_bit() {
+        match match_sgr_mouse_event(b"32;5;10M") {
+            Match::Event(Event::Mouse { button, drag, .. }, _) => {
+                assert_eq!(button, 0);
+                assert!(drag);
+            }
+            other => panic!("expected a mouse event, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn sgr_mouse_event_is_partial_without_a_terminator() {
+        assert_eq!(match_sgr_mouse_event(b"0;5;10"), Match::Partial);
+    }
+}
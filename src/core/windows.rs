@@ -0,0 +1,188 @@
+//! Windows console backend. `term::terminfo` has no concept of a Windows console, so this
+//! drives the screen and reads input through the Win32 Console API directly instead.
+//!
+//! Unlike the terminfo path, Win32 console functions (`SetConsoleTextAttribute`, etc.) take
+//! effect immediately rather than producing bytes to write to a stream. `get` below performs
+//! the call itself and returns an empty (but `Some`) buffer on success, so `Driver::get` keeps
+//! the same "bytes to write" signature across both backends without the caller needing to
+//! know which one it's talking to.
+
+// `stdin` is kept for the input-reading loop that builds the `buf` passed to `feed`, which
+// lives outside this backend.
+#![allow(dead_code)]
+
+use std::io::Error;
+use std::mem;
+use std::str;
+
+use winapi::shared::minwindef::{DWORD, WORD};
+use winapi::um::handleapi::INVALID_HANDLE_VALUE;
+use winapi::um::processenv::GetStdHandle;
+use winapi::um::winbase::{STD_INPUT_HANDLE, STD_OUTPUT_HANDLE};
+use winapi::um::wincon::{self, CONSOLE_CURSOR_INFO, COORD};
+use winapi::um::winnt::HANDLE;
+
+use core::backend::{Backend, DevFn};
+use core::input::{Event, Match};
+
+// Virtual-key codes for the keys this backend translates into `Event`s. Anything else with a
+// printable character attached falls back to `Event::Char`.
+const VK_PRIOR: u16 = 0x21; // Page Up
+const VK_NEXT: u16 = 0x22; // Page Down
+const VK_END: u16 = 0x23;
+const VK_HOME: u16 = 0x24;
+const VK_LEFT: u16 = 0x25;
+const VK_UP: u16 = 0x26;
+const VK_RIGHT: u16 = 0x27;
+const VK_DOWN: u16 = 0x28;
+const VK_F1: u16 = 0x70;
+const VK_F12: u16 = 0x7b;
+
+pub struct WindowsBackend {
+    stdin: HANDLE,
+    stdout: HANDLE,
+}
+
+impl WindowsBackend {
+    pub fn new() -> Result<WindowsBackend, Error> {
+        let stdin = unsafe { GetStdHandle(STD_INPUT_HANDLE) };
+        let stdout = unsafe { GetStdHandle(STD_OUTPUT_HANDLE) };
+
+        if stdin == INVALID_HANDLE_VALUE || stdout == INVALID_HANDLE_VALUE {
+            return Err(Error::last_os_error());
+        }
+
+        Ok(WindowsBackend {
+            stdin: stdin,
+            stdout: stdout,
+        })
+    }
+
+    // Maps a `DevFn::SetFg`/`SetBg` 256-color index onto a `SetConsoleTextAttribute` attribute
+    // word. The console only has the 16 legacy colors, so this keeps just the low 3 bits plus
+    // intensity rather than the full 256-color range terminfo gets.
+    fn color_attr(attr: u8, foreground: bool) -> WORD {
+        let (r, g, b, intensity) = if foreground {
+            (wincon::FOREGROUND_RED, wincon::FOREGROUND_GREEN, wincon::FOREGROUND_BLUE,
+             wincon::FOREGROUND_INTENSITY)
+        } else {
+            (wincon::BACKGROUND_RED, wincon::BACKGROUND_GREEN, wincon::BACKGROUND_BLUE,
+             wincon::BACKGROUND_INTENSITY)
+        };
+
+        let mut word = 0;
+        if attr & 0b001 != 0 {
+            word |= r;
+        }
+        if attr & 0b010 != 0 {
+            word |= g;
+        }
+        if attr & 0b100 != 0 {
+            word |= b;
+        }
+        if attr & 0b1000 != 0 {
+            word |= intensity;
+        }
+
+        word
+    }
+
+    fn set_cursor_visible(&self, visible: bool) -> Option<Vec<u8>> {
+        let mut info: CONSOLE_CURSOR_INFO = unsafe { mem::zeroed() };
+        info.dwSize = 100;
+        info.bVisible = visible as i32;
+
+        let ok = unsafe { wincon::SetConsoleCursorInfo(self.stdout, &info) };
+        if ok == 0 { None } else { Some(Vec::new()) }
+    }
+}
+
+impl Backend for WindowsBackend {
+    // Translates a console `KEY_EVENT_RECORD` into the same `Event` variants the terminfo path
+    // produces. `buf` holds `wVirtualKeyCode` and `UnicodeChar` encoded as UTF-8, one char each
+    // (`'\0'` for `UnicodeChar` when absent); unlike the Unix backend's raw escape bytes, this
+    // is this backend's own encoding of the two fields, so decoding it as `&str` is safe.
+    //
+    // Each console input record arrives whole (there's no byte-at-a-time stream to resync),
+    // so this never has a `Match::Partial` case: a record either translates to an `Event` or
+    // it doesn't.
+    fn feed(&self, buf: &[u8]) -> Match {
+        let s = match str::from_utf8(buf) {
+            Ok(s) => s,
+            Err(_) => return Match::None,
+        };
+        let mut chars = s.chars();
+        let vk_code = chars.next().map(|c| c as u16).unwrap_or(0);
+        let ch = chars.next().unwrap_or('\0');
+
+        let event = match vk_code {
+            VK_UP => Some(Event::Up),
+            VK_DOWN => Some(Event::Down),
+            VK_LEFT => Some(Event::Left),
+            VK_RIGHT => Some(Event::Right),
+            VK_PRIOR => Some(Event::PageUp),
+            VK_NEXT => Some(Event::PageDown),
+            VK_HOME => Some(Event::Home),
+            VK_END => Some(Event::End),
+            VK_F1...VK_F12 => Some(Event::Function((vk_code - VK_F1 + 1) as u8)),
+            _ if ch != '\0' => Some(Event::Char(ch)),
+            _ => None,
+        };
+
+        match event {
+            Some(event) => Match::Event(event, buf.len()),
+            None => Match::None,
+        }
+    }
+
+    fn get(&self, dfn: DevFn) -> Option<Vec<u8>> {
+        let ok = match dfn {
+            DevFn::SetFg(attr) => unsafe {
+                wincon::SetConsoleTextAttribute(self.stdout, WindowsBackend::color_attr(attr, true))
+            },
+            DevFn::SetBg(attr) => unsafe {
+                wincon::SetConsoleTextAttribute(self.stdout, WindowsBackend::color_attr(attr, false))
+            },
+            DevFn::SetCursor(x, y) => {
+                let pos = COORD {
+                    X: x as i16,
+                    Y: y as i16,
+                };
+                unsafe { wincon::SetConsoleCursorPosition(self.stdout, pos) }
+            }
+            DevFn::Clear => {
+                let mut info = unsafe { mem::zeroed() };
+                if unsafe { wincon::GetConsoleScreenBufferInfo(self.stdout, &mut info) } == 0 {
+                    return None;
+                }
+
+                let size = (info.dwSize.X as DWORD) * (info.dwSize.Y as DWORD);
+                let origin = COORD { X: 0, Y: 0 };
+                let mut written: DWORD = 0;
+                unsafe {
+                    wincon::FillConsoleOutputCharacterA(self.stdout, b' ' as i8, size, origin, &mut written)
+                }
+            }
+            DevFn::ShowCursor => return self.set_cursor_visible(true),
+            DevFn::HideCursor => return self.set_cursor_visible(false),
+            // No direct Win32 console equivalent: the alternate screen buffer, keypad
+            // transmit mode and text attributes below aren't modeled by this backend yet.
+            DevFn::EnterCa |
+            DevFn::ExitCa |
+            DevFn::EnterXmit |
+            DevFn::ExitXmit |
+            DevFn::Reset |
+            DevFn::Underline |
+            DevFn::Bold |
+            DevFn::Blink |
+            DevFn::Reverse |
+            // The Win32 console has no xterm-style mouse tracking protocol to toggle; mouse
+            // input there comes from `ENABLE_MOUSE_INPUT` console mode, which this backend
+            // doesn't set up yet.
+            DevFn::EnableMouse |
+            DevFn::DisableMouse => return None,
+        };
+
+        if ok == 0 { None } else { Some(Vec::new()) }
+    }
+}
@@ -4,9 +4,14 @@
 extern crate bitflags;
 extern crate libc;
 extern crate nix;
+#[cfg(unix)]
+extern crate term;
+#[cfg(windows)]
+extern crate winapi;
 
 pub mod core;
 pub mod util;
+mod terminal;
 
 pub use self::core::{Terminal, Cell, Color, Style, Attr};
 pub use self::util::Error;
\ No newline at end of file
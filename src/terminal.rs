@@ -1,20 +1,11 @@
-const XTERM_FUNCS: &'static [&'static str] = &[
-    "\x1b[?1049h",
-    "\x1b[?1049l",
-    "\x1b[?12l\x1b[?25h",
-    "\x1b[?25l",
-    "\x1b[H\x1b[2J",
-    "\x1b(B\x1b[m",
-    "\x1b[4m",
-    "\x1b[1m",
-    "\x1b[5m",
-    "\x1b[7m",
-    "\x1b[?1h\x1b=",
-    "\x1b[?1l\x1b>",
-    "\x1b[?1000h",
-    "\x1b[?1000l",
-];
+//! Built-in terminal definitions, used as a fallback when no terminfo database is available.
+//!
+//! The entries here are indexed to align with the `KEYS` table in `core::unix` and the `DevFn`
+//! enum in `core::backend`, so `UnixBackend` can serve escape sequences straight out of
+//! `keys`/`funcs` without needing a `TermInfo` at all.
 
+// Order matches `core::unix::KEYS`: f1-f12, then up/down/left/right, page up/down,
+// home/end.
 const XTERM_KEYS: &'static [&'static str] = &[
     "\x1bOP",
     "\x1bOQ",
@@ -28,28 +19,94 @@ const XTERM_KEYS: &'static [&'static str] = &[
     "\x1b[21~",
     "\x1b[23~",
     "\x1b[24~",
-    "\x1b[2~",
-    "\x1b[3~",
-    "\x1bOH",
-    "\x1bOF",
-    "\x1b[5~",
-    "\x1b[6~",
     "\x1bOA",
     "\x1bOB",
     "\x1bOD",
     "\x1bOC",
+    "\x1b[5~",
+    "\x1b[6~",
+    "\x1bOH",
+    "\x1bOF",
+];
+
+// Order matches the non-parameterized variants of `core::driver::DevFn`: enter/exit ca,
+// enter/exit xmit, show/hide cursor, clear, reset, underline, bold, blink, reverse.
+//
+// The mouse tracking toggle isn't in here: it's not a terminfo capability either, so
+// `UnixBackend` emits it as a fixed xterm sequence for both the terminfo and built-in paths
+// instead of sourcing it from a per-terminal table.
+const XTERM_FUNCS: &'static [&'static str] = &[
+    "\x1b[?1049h",
+    "\x1b[?1049l",
+    "\x1b[?1h\x1b=",
+    "\x1b[?1l\x1b>",
+    "\x1b[?12l\x1b[?25h",
+    "\x1b[?25l",
+    "\x1b[H\x1b[2J",
+    "\x1b(B\x1b[m",
+    "\x1b[4m",
+    "\x1b[1m",
+    "\x1b[5m",
+    "\x1b[7m",
 ];
 
 pub struct Terminal {
-    name: &'static str,
-    keys: &'static [&'static str],
-    funcs: &'static [&'static str],
+    pub name: &'static str,
+    pub keys: &'static [&'static str],
+    pub funcs: &'static [&'static str],
 }
 
-const terminals: &'static [Terminal] = &[
-    Terminal {
-        name: "xterm",
-        keys: XTERM_KEYS,
-        funcs: XTERM_FUNCS,
-    },
-];
\ No newline at end of file
+// The one built-in definition: plain ANSI/xterm escape sequences, good enough for every
+// prefix in `ANSI_TERM_PREFIXES` (they're all xterm-compatible, not distinct terminal types
+// with their own tables).
+const XTERM: Terminal = Terminal {
+    name: "xterm",
+    keys: XTERM_KEYS,
+    funcs: XTERM_FUNCS,
+};
+
+// Prefixes of `$TERM` values known to be ANSI/xterm compatible. Kept sorted so `lookup` can
+// binary-search it, including for suffixed variants (e.g. "xterm-256color", "screen.xterm-256color").
+const ANSI_TERM_PREFIXES: &'static [&'static str] =
+    &["Eterm", "ansi", "konsole", "linux", "rxvt", "screen", "tmux", "xterm"];
+
+// Finds the built-in `Terminal` definition matching `$TERM`, if any.
+//
+// `term` is matched against `ANSI_TERM_PREFIXES` by binary search; an exact match is used
+// directly, and otherwise the preceding entry is checked with `starts_with` to catch suffixed
+// variants like `xterm-256color`. Every prefix here is ANSI/xterm compatible, so a match just
+// means `term` gets the one `XTERM` definition, not a per-prefix table.
+pub fn lookup(term: &str) -> Option<&'static Terminal> {
+    let matched = match ANSI_TERM_PREFIXES.binary_search(&term) {
+        Ok(_) => true,
+        Err(idx) => idx > 0 && term.starts_with(ANSI_TERM_PREFIXES[idx - 1]),
+    };
+
+    if matched { Some(&XTERM) } else { None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::lookup;
+
+    #[test]
+    fn matches_bare_prefixes() {
+        for term in &["linux", "ansi", "xterm", "screen", "tmux", "rxvt", "Eterm", "konsole"] {
+            assert!(lookup(term).is_some(), "expected {} to match", term);
+        }
+    }
+
+    #[test]
+    fn matches_suffixed_variants() {
+        for term in &["xterm-256color", "screen-256color", "tmux-256color", "Eterm-color",
+                       "konsole-256color"] {
+            assert!(lookup(term).is_some(), "expected {} to match", term);
+        }
+    }
+
+    #[test]
+    fn rejects_unrelated_terminals() {
+        assert!(lookup("dumb").is_none());
+        assert!(lookup("vt100").is_none());
+    }
+}